@@ -0,0 +1,169 @@
+//! SQLite-backed cache of `describe_instances` results, modeled on
+//! build-o-tron's `dbctx` module: a single file under the user's cache dir,
+//! one row per query shape, so a repeat invocation can show a picker full of
+//! instances before AWS answers instead of blocking on the round-trip.
+//!
+//! A row is keyed by a hash of the query shape (profile, region, filters) and
+//! holds the JSON-serialized result plus the UNIX timestamp it was fetched
+//! at; callers decide for themselves whether that's fresh enough to trust
+//! outright or just good enough to show while a refresh is in flight.
+
+use aws_sdk_ec2::model::{Instance, InstanceState, InstanceStateName, InstanceType, Tag};
+use aws_smithy_types::DateTime;
+use eyre::{eyre, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+/// The subset of `Instance` the cache actually persists. The generated AWS
+/// SDK model types don't derive `Serialize`/`Deserialize` (only builders and
+/// `Debug`/`Clone`/`PartialEq`), so a real `Instance` can't round-trip
+/// through `serde_json` directly — this DTO carries just the fields the
+/// picker/preview use and converts at the cache boundary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedInstance {
+    instance_id: Option<String>,
+    instance_type: Option<String>,
+    state: Option<String>,
+    launch_time: Option<i64>,
+    public_dns_name: Option<String>,
+    private_dns_name: Option<String>,
+    tags: Vec<(String, String)>,
+}
+
+impl From<&Instance> for CachedInstance {
+    fn from(instance: &Instance) -> Self {
+        Self {
+            instance_id: instance.instance_id.clone(),
+            instance_type: instance
+                .instance_type
+                .as_ref()
+                .map(|t| t.as_str().to_string()),
+            state: instance
+                .state
+                .as_ref()
+                .and_then(|s| s.name.as_ref())
+                .map(|n| n.as_str().to_string()),
+            launch_time: instance.launch_time.as_ref().map(DateTime::secs),
+            public_dns_name: instance.public_dns_name.clone(),
+            private_dns_name: instance.private_dns_name.clone(),
+            tags: instance
+                .tags
+                .as_ref()
+                .map(|tags| {
+                    tags.iter()
+                        .map(|t| (t.key.clone().unwrap_or_default(), t.value.clone().unwrap_or_default()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<CachedInstance> for Instance {
+    fn from(cached: CachedInstance) -> Self {
+        Instance::builder()
+            .set_instance_id(cached.instance_id)
+            .set_instance_type(cached.instance_type.map(|t| InstanceType::from(t.as_str())))
+            .set_state(cached.state.map(|name| {
+                InstanceState::builder()
+                    .name(InstanceStateName::from(name.as_str()))
+                    .build()
+            }))
+            .set_launch_time(cached.launch_time.map(DateTime::from_secs))
+            .set_public_dns_name(cached.public_dns_name)
+            .set_private_dns_name(cached.private_dns_name)
+            .set_tags(Some(
+                cached
+                    .tags
+                    .into_iter()
+                    .map(|(key, value)| Tag::builder().key(key).value(value).build())
+                    .collect(),
+            ))
+            .build()
+    }
+}
+
+pub struct Cache {
+    conn: Connection,
+}
+
+impl Cache {
+    pub fn open() -> Result<Self> {
+        let path = cache_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)?;
+        // Several `query_source` tasks (one per --profile/--region pair) can
+        // open this same file concurrently; without a busy timeout a writer
+        // that loses the race gets SQLITE_BUSY instead of waiting its turn.
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS instances (
+                key TEXT PRIMARY KEY,
+                payload BLOB NOT NULL,
+                fetched_at INTEGER NOT NULL
+            )",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Look up a cached row regardless of age; the caller decides what counts
+    /// as stale.
+    pub fn get(&self, key: &str) -> Result<Option<(Vec<Instance>, i64)>> {
+        let row: Option<(Vec<u8>, i64)> = self
+            .conn
+            .query_row(
+                "SELECT payload, fetched_at FROM instances WHERE key = ?1",
+                params![key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        row.map(|(payload, fetched_at)| {
+            let cached: Vec<CachedInstance> = serde_json::from_slice(&payload)
+                .map_err(|e| eyre!("failed to decode cached instances: {}", e))?;
+            let instances = cached.into_iter().map(Instance::from).collect();
+            Ok((instances, fetched_at))
+        })
+        .transpose()
+    }
+
+    /// Overwrite the cached row for `key` with a fresh result set. A failed
+    /// refresh should never call this, so the previous (still-usable) row is
+    /// never clobbered with nothing.
+    pub fn put(&self, key: &str, instances: &[Instance], fetched_at: i64) -> Result<()> {
+        let cached: Vec<CachedInstance> = instances.iter().map(CachedInstance::from).collect();
+        let payload = serde_json::to_vec(&cached)
+            .map_err(|e| eyre!("failed to encode instances for cache: {}", e))?;
+        self.conn.execute(
+            "INSERT INTO instances (key, payload, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET payload = excluded.payload, fetched_at = excluded.fetched_at",
+            params![key, payload, fetched_at],
+        )?;
+        Ok(())
+    }
+}
+
+fn cache_path() -> Result<PathBuf> {
+    let mut dir = dirs::cache_dir().ok_or_else(|| eyre!("no cache dir on this platform"))?;
+    dir.push("skim-ec2");
+    dir.push("instances.sqlite");
+    Ok(dir)
+}
+
+/// Key a cache row by the shape of the query that produced it: profile,
+/// region, and filters (order-independent).
+pub fn cache_key(profile: Option<&str>, region: Option<&str>, filters: &[String]) -> String {
+    let mut sorted = filters.to_vec();
+    sorted.sort();
+
+    let mut hasher = DefaultHasher::new();
+    profile.hash(&mut hasher);
+    region.hash(&mut hasher);
+    sorted.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
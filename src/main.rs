@@ -8,8 +8,10 @@
 // allow expect
 #![allow(clippy::expect_used)]
 
+mod cache;
+
 use aws_sdk_ec2 as ec2;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use colored_json::to_colored_json_auto;
 use ec2::{model::Instance, Client};
 use eyre::{eyre, Result};
@@ -19,9 +21,27 @@ use std::os::unix::process::CommandExt;
 use std::{borrow::Cow, collections::HashMap};
 
 #[derive(Parser, Debug)]
-struct Args {
-    #[arg(short, long, value_name = "PROFILE")]
-    profile: Option<String>,
+struct Cli {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Options shared by every subcommand: which instances to query and how to
+/// name them.
+#[derive(clap::Args, Debug, Clone)]
+struct CommonArgs {
+    /// AWS profile to query. Repeat or comma-separate to query several
+    /// profiles in parallel.
+    #[arg(short, long, value_name = "PROFILE", value_delimiter = ',')]
+    profile: Vec<String>,
+
+    /// AWS region to query. Repeat or comma-separate to query several
+    /// regions in parallel. Defaults to the profile's configured region.
+    #[arg(short, long, value_name = "REGION", value_delimiter = ',')]
+    region: Vec<String>,
 
     #[arg(short, long, value_name = "NAME=VALUE")]
     filter: Vec<String>,
@@ -35,8 +55,52 @@ struct Args {
     #[arg(long)]
     name_id: bool,
 
-    #[arg(short, long, value_name = "COMMAND")]
-    command: Option<String>,
+    /// Max age, in seconds, for a cached result to count as fresh. Cached
+    /// results are shown at startup whatever their age while the live refresh
+    /// runs in the background; older ones are marked stale in the preview.
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    cache_ttl: u64,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Pick an instance interactively with skim, then connect to it.
+    Connect {
+        #[arg(short, long, value_name = "COMMAND")]
+        command: Option<String>,
+
+        /// Connect via `ssh` to the selected instance's public or private DNS
+        /// name. Falls back to an SSM session if no reachable DNS name is
+        /// found.
+        #[arg(long)]
+        ssh: bool,
+
+        /// User to pass to `ssh` as `user@host`. Defaults to ssh's own default.
+        #[arg(long, value_name = "USER")]
+        ssh_user: Option<String>,
+
+        /// Connect via `aws ssm start-session` to the selected instance.
+        #[arg(long)]
+        ssm: bool,
+    },
+    /// Print matching instances non-interactively, for scripts and pipelines.
+    List {
+        #[arg(long, value_enum, default_value_t = ListFormat::Json)]
+        format: ListFormat,
+    },
+    /// Print the details of a single instance as colored JSON.
+    Describe {
+        /// Instance ID to describe, e.g. `i-0123456789abcdef0`.
+        instance_id: String,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ListFormat {
+    /// One JSON object per line.
+    Json,
+    /// A plain tab-separated table.
+    Table,
 }
 
 #[derive(Debug, Clone)]
@@ -50,11 +114,94 @@ enum NameRule {
 struct InstanceItem {
     instance: Instance,
     name_rule: Box<NameRule>,
+    /// Set when this item was loaded from the on-disk cache and is older
+    /// than `--cache-ttl`; surfaced in the preview so a picked instance's
+    /// staleness is obvious rather than silently shown as current.
+    stale: bool,
+    /// Which `--profile`/`--region` this instance was fetched from, so a
+    /// picker full of results from several accounts stays navigable.
+    source_profile: Option<String>,
+    source_region: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 struct ErrorItem {
     message: String,
+    source_profile: Option<String>,
+    source_region: Option<String>,
+}
+
+/// Resolve a reachable DNS name for an instance, preferring the public name.
+fn resolve_host(instance: &Instance) -> Option<&str> {
+    instance
+        .public_dns_name
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .or_else(|| instance.private_dns_name.as_deref().filter(|s| !s.is_empty()))
+}
+
+/// Human-readable label for the (profile, region) an instance or error came
+/// from, e.g. `"prod/us-east-1"`.
+fn source_label(profile: Option<&str>, region: Option<&str>) -> String {
+    match (profile, region) {
+        (Some(p), Some(r)) => format!("{p}/{r}"),
+        (Some(p), None) => p.to_string(),
+        (None, Some(r)) => r.to_string(),
+        (None, None) => "default".to_string(),
+    }
+}
+
+/// Build the colored-JSON-friendly view of an instance shared by the skim
+/// preview, `list --format json`, and `describe`.
+fn instance_json(instance: &Instance, stale: bool, source: &str) -> Value {
+    let instance_type = instance
+        .instance_type
+        .as_ref()
+        .expect("instance has no type");
+    let instance_state = instance
+        .state
+        .as_ref()
+        .expect("instance has no state")
+        .name
+        .as_ref()
+        .expect("instance state name")
+        .as_str();
+    let tags: HashMap<String, String> = instance
+        .tags
+        .as_ref()
+        .into_iter()
+        .flatten()
+        .map(|t| {
+            (
+                t.key.as_ref().expect("tag key").to_string(),
+                t.value.as_ref().expect("tag value").to_string(),
+            )
+        })
+        .collect();
+
+    let uptime = match instance.launch_time {
+        Some(ref x) => {
+            let secs = x.secs();
+            let now = chrono::Utc::now().timestamp();
+            let uptime = secs - now;
+            let uptime = chrono::Duration::seconds(uptime);
+            let uptime = chrono_humanize::HumanTime::from(uptime);
+            format!("{}", uptime)
+        }
+        None => String::new(),
+    };
+
+    json!({
+        "instance_id": instance.instance_id.as_ref().expect("instance id"),
+        "instance_type": instance_type.as_str(),
+        "state": instance_state,
+        "uptime": uptime,
+        "public_dns_name": instance.public_dns_name.as_ref(),
+        "private_dns_name": instance.private_dns_name.as_ref(),
+        "tags": tags,
+        "stale": stale,
+        "source": source
+    })
 }
 
 impl<'a> From<Instance> for InstanceItem {
@@ -62,17 +209,27 @@ impl<'a> From<Instance> for InstanceItem {
         Self {
             instance: val,
             name_rule: Box::new(NameRule::InstanceID),
+            stale: false,
+            source_profile: None,
+            source_region: None,
         }
     }
 }
 
 impl SkimItem for ErrorItem {
     fn text(&self) -> Cow<str> {
-        Cow::from("error")
+        Cow::from(format!(
+            "error: {}",
+            source_label(self.source_profile.as_deref(), self.source_region.as_deref())
+        ))
     }
 
     fn preview(&self, _context: PreviewContext) -> ItemPreview {
-        ItemPreview::Text(self.message.clone())
+        ItemPreview::Text(format!(
+            "{}: {}",
+            source_label(self.source_profile.as_deref(), self.source_region.as_deref()),
+            self.message
+        ))
     }
 }
 
@@ -80,23 +237,22 @@ impl SkimItem for InstanceItem {
     fn text(&self) -> Cow<str> {
         match *self.name_rule {
             NameRule::Tag(ref tag) => {
-                let tags = self.instance.tags.as_ref().expect("instance has no tags");
-                let name = tags
-                    .iter()
-                    .find(|t| t.key == Some(tag.to_string()))
-                    .expect("tag for name not found")
-                    .value
+                // Untagged instances (or instances missing this particular
+                // tag) fall back to an empty name rather than panicking —
+                // `list --format table` runs this over every matched instance,
+                // so a missing tag can no longer be treated as unreachable.
+                let name = self
+                    .instance
+                    .tags
                     .as_ref()
-                    .expect("tag for name has no value");
+                    .into_iter()
+                    .flatten()
+                    .find(|t| t.key == Some(tag.to_string()))
+                    .and_then(|t| t.value.as_deref())
+                    .unwrap_or("");
                 Cow::from(name)
             }
-            NameRule::Host => Cow::from(match self.instance.public_dns_name {
-                Some(ref x) => x,
-                None => match self.instance.private_dns_name {
-                    Some(ref x) => x,
-                    None => "",
-                },
-            }),
+            NameRule::Host => Cow::from(resolve_host(&self.instance).unwrap_or("")),
             NameRule::InstanceID => Cow::from(match self.instance.instance_id {
                 Some(ref x) => x,
                 None => "",
@@ -109,55 +265,11 @@ impl SkimItem for InstanceItem {
     }
 
     fn preview(&self, _context: PreviewContext) -> ItemPreview {
-        let instance_type = self
-            .instance
-            .instance_type
-            .as_ref()
-            .expect("instance has no type");
-        let instance_state = self
-            .instance
-            .state
-            .as_ref()
-            .expect("instance has no state")
-            .name
-            .as_ref()
-            .expect("instance state name")
-            .as_str();
-        let tags: HashMap<String, String> = self
-            .instance
-            .tags
-            .as_ref()
-            .expect("instance tags")
-            .iter()
-            .map(|t| {
-                return (
-                    t.key.as_ref().expect("tag key").to_string(),
-                    t.value.as_ref().expect("tag value").to_string(),
-                );
-            })
-            .collect();
-
-        let uptime = match self.instance.launch_time {
-            Some(ref x) => {
-                let secs = x.secs();
-                let now = chrono::Utc::now().timestamp();
-                let uptime = secs - now;
-                let uptime = chrono::Duration::seconds(uptime);
-                let uptime = chrono_humanize::HumanTime::from(uptime);
-                format!("{}", uptime)
-            }
-            None => String::new(),
-        };
-
-        let val: Value = json!({
-            "instance_id":  self.instance.instance_id.as_ref().expect("instance id"),
-            "instance_type": instance_type.as_str(),
-            "state": instance_state,
-            "uptime": uptime,
-            "public_dns_name": self.instance.public_dns_name.as_ref(),
-            "private_dns_name":  self.instance.private_dns_name.as_ref(),
-            "tags": tags
-        });
+        let val = instance_json(
+            &self.instance,
+            self.stale,
+            &source_label(self.source_profile.as_deref(), self.source_region.as_deref()),
+        );
         let s = to_colored_json_auto(&val).unwrap_or_else(|_| String::new());
         ItemPreview::AnsiText(s)
     }
@@ -179,22 +291,33 @@ impl SkimItem for InstanceItem {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let mut args = Args::parse();
+    let mut cli = Cli::parse();
 
-    if args.filter.is_empty() {
-        args.filter.push("instance-state-name=running".to_string());
+    if cli.common.filter.is_empty() {
+        cli.common
+            .filter
+            .push("instance-state-name=running".to_string());
     }
 
-    if let Some(ref profile) = args.profile {
-        std::env::set_var("AWS_PROFILE", profile);
+    match cli.command {
+        Command::Connect {
+            command,
+            ssh,
+            ssh_user,
+            ssm,
+        } => connect(cli.common, command, ssh, ssh_user, ssm).await,
+        Command::List { format } => list(&cli.common, format).await,
+        Command::Describe { instance_id } => describe(&cli.common, &instance_id).await,
     }
+}
 
-    let config = aws_config::load_from_env().await;
-    // verify credentials
-    let _ = config.credentials_provider();
-
-    let client = ec2::Client::new(&config);
-
+async fn connect(
+    common: CommonArgs,
+    command: Option<String>,
+    ssh: bool,
+    ssh_user: Option<String>,
+    ssm: bool,
+) -> Result<()> {
     let options = SkimOptionsBuilder::default()
         .height(Some("100%"))
         .multi(false)
@@ -203,8 +326,9 @@ async fn main() -> Result<()> {
         .build()
         .expect("failed to build skim options");
 
-    let args = Arc::new(args);
-    let r = get_instances_background(Arc::new(client), args.clone()).await;
+    let common = Arc::new(common);
+    let instance_index: InstanceIndex = Arc::new(std::sync::Mutex::new(HashMap::new()));
+    let r = get_instances_background(common.clone(), instance_index.clone()).await;
 
     let output = Skim::run_with(&options, Some(r)).ok_or_else(|| eyre!("No output from skim"))?;
     let instance_id: String = if output.is_abort {
@@ -218,7 +342,22 @@ async fn main() -> Result<()> {
             .to_string())
     }?;
 
-    if let Some(cmdline) = args.command.as_ref() {
+    if ssh || ssm {
+        let (instance, source_profile, source_region) = instance_index
+            .lock()
+            .expect("instance index lock poisoned")
+            .get(&instance_id)
+            .cloned()
+            .ok_or_else(|| eyre!("selected instance not found"))?;
+        if ssh {
+            match resolve_host(&instance) {
+                Some(_) => connect_ssh(&instance, ssh_user.as_deref()),
+                None => connect_ssm(&instance_id, source_profile.as_deref(), source_region.as_deref()),
+            }
+        } else {
+            connect_ssm(&instance_id, source_profile.as_deref(), source_region.as_deref())
+        }
+    } else if let Some(cmdline) = command.as_ref() {
         let id = shell_escape::escape(std::borrow::Cow::Borrowed(&instance_id));
         let cmdline = if cmdline.contains("{}") {
             cmdline.replace("{}", &id)
@@ -234,31 +373,281 @@ async fn main() -> Result<()> {
     }
 }
 
-async fn get_instances_background(client: Arc<Client>, args: Arc<Args>) -> SkimItemReceiver {
-    let (s, r) = unbounded();
-    tokio::spawn(async move {
-        match get_instances(&client, &args).await {
-            Ok(instances) => {
-                for item in instances {
-                    let x: Arc<dyn SkimItem> = Arc::new(item.clone());
-                    s.send(x).expect("send error");
+/// Fetch matching instances from every `--profile`/`--region` source
+/// concurrently and wait for all of them, for the non-interactive modes
+/// where there's no picker to stream into.
+async fn list_instances(common: &CommonArgs) -> Vec<InstanceItem> {
+    let mut handles = Vec::new();
+    for (profile, region) in query_targets(common) {
+        let common = common.clone();
+        handles.push(tokio::spawn(async move {
+            let client = ec2_client(profile.as_deref(), region.as_deref()).await;
+            match get_instances(&client, &common).await {
+                Ok(mut instances) => {
+                    for item in &mut instances {
+                        item.source_profile = profile.clone();
+                        item.source_region = region.clone();
+                    }
+                    instances
+                }
+                Err(err) => {
+                    eprintln!(
+                        "{}: {}",
+                        source_label(profile.as_deref(), region.as_deref()),
+                        err
+                    );
+                    Vec::new()
                 }
             }
-            Err(msg) => {
+        }));
+    }
+
+    let mut all = Vec::new();
+    for handle in handles {
+        if let Ok(instances) = handle.await {
+            all.extend(instances);
+        }
+    }
+    all
+}
+
+async fn list(common: &CommonArgs, format: ListFormat) -> Result<()> {
+    let instances = list_instances(common).await;
+    match format {
+        ListFormat::Json => {
+            for item in &instances {
+                let val = instance_json(
+                    &item.instance,
+                    item.stale,
+                    &source_label(item.source_profile.as_deref(), item.source_region.as_deref()),
+                );
+                println!("{}", serde_json::to_string(&val)?);
+            }
+        }
+        ListFormat::Table => {
+            for item in &instances {
+                println!(
+                    "{}\t{}\t{}",
+                    item.instance.instance_id.as_deref().unwrap_or(""),
+                    item.text(),
+                    source_label(item.source_profile.as_deref(), item.source_region.as_deref()),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Look up a single instance by ID across every configured `--profile`/
+/// `--region` source, the same way `connect` and `list` do, rather than only
+/// checking the first one.
+async fn describe(common: &CommonArgs, instance_id: &str) -> Result<()> {
+    let mut common = common.clone();
+    common.filter = vec![format!("instance-id={instance_id}")];
+
+    let item = list_instances(&common)
+        .await
+        .into_iter()
+        .next()
+        .ok_or_else(|| eyre!("instance {} not found", instance_id))?;
+
+    let val = instance_json(
+        &item.instance,
+        item.stale,
+        &source_label(item.source_profile.as_deref(), item.source_region.as_deref()),
+    );
+    println!("{}", to_colored_json_auto(&val)?);
+    Ok(())
+}
+
+/// Exec `ssh <user>@<host>` (or just `<host>` with no user) for the instance's
+/// resolved DNS name, replacing the current process.
+fn connect_ssh(instance: &Instance, user: Option<&str>) -> Result<()> {
+    let host = resolve_host(instance).ok_or_else(|| eyre!("instance has no reachable dns name"))?;
+    let target = match user {
+        Some(user) => format!("{}@{}", user, host),
+        None => host.to_string(),
+    };
+    let err = std::process::Command::new("ssh").arg(target).exec();
+    Err(eyre!("failed to exec ssh: {}", err))
+}
+
+/// Exec `aws ssm start-session --target <instance-id>`, replacing the current process.
+fn connect_ssm(instance_id: &str, profile: Option<&str>, region: Option<&str>) -> Result<()> {
+    let mut cmd = std::process::Command::new("aws");
+    cmd.arg("ssm")
+        .arg("start-session")
+        .arg("--target")
+        .arg(instance_id);
+    if let Some(profile) = profile {
+        cmd.arg("--profile").arg(profile);
+    }
+    if let Some(region) = region {
+        cmd.arg("--region").arg(region);
+    }
+    let err = cmd.exec();
+    Err(eyre!("failed to exec aws ssm start-session: {}", err))
+}
+
+/// Instance ID -> (`Instance`, source profile, source region) lookup,
+/// populated as results stream in, so the connect modes can recover full
+/// instance data — and which profile/region it came from — for a skim
+/// selection (which only carries the instance ID through as its `output()`).
+type InstanceIndex = Arc<std::sync::Mutex<HashMap<String, (Instance, Option<String>, Option<String>)>>>;
+
+fn index_instance(instance_index: &InstanceIndex, item: &InstanceItem) {
+    if let Some(ref id) = item.instance.instance_id {
+        instance_index.lock().expect("instance index lock poisoned").insert(
+            id.clone(),
+            (
+                item.instance.clone(),
+                item.source_profile.clone(),
+                item.source_region.clone(),
+            ),
+        );
+    }
+}
+
+/// Every (profile, region) pair to query, taken as the cartesian product of
+/// `--profile` and `--region` (each defaulting to a single "unset" slot so a
+/// bare invocation still queries once, using the environment's defaults).
+fn query_targets(common: &CommonArgs) -> Vec<(Option<String>, Option<String>)> {
+    let profiles: Vec<Option<String>> = if common.profile.is_empty() {
+        vec![None]
+    } else {
+        common.profile.iter().cloned().map(Some).collect()
+    };
+    let regions: Vec<Option<String>> = if common.region.is_empty() {
+        vec![None]
+    } else {
+        common.region.iter().cloned().map(Some).collect()
+    };
+
+    profiles
+        .into_iter()
+        .flat_map(|p| regions.iter().cloned().map(move |r| (p.clone(), r)))
+        .collect()
+}
+
+async fn ec2_client(profile: Option<&str>, region: Option<&str>) -> Client {
+    let mut loader = aws_config::from_env();
+    if let Some(profile) = profile {
+        loader = loader.profile_name(profile);
+    }
+    if let Some(region) = region {
+        loader = loader.region(ec2::Region::new(region.to_string()));
+    }
+    Client::new(&loader.load().await)
+}
+
+/// Fan out one background task per `--profile`/`--region` source, each
+/// streaming its results into the shared channel as they arrive. `Skim::run_with`
+/// drops its receiver as soon as the user picks (or aborts), so a source still
+/// in flight at that point finds the channel closed; that's expected, not an
+/// error, so sends here never unwrap their result.
+async fn get_instances_background(
+    common: Arc<CommonArgs>,
+    instance_index: InstanceIndex,
+) -> SkimItemReceiver {
+    let (s, r) = unbounded();
+
+    for (profile, region) in query_targets(&common) {
+        let common = common.clone();
+        let instance_index = instance_index.clone();
+        let s = s.clone();
+        tokio::spawn(async move {
+            if let Err(err) =
+                query_source(&common, profile.as_deref(), region.as_deref(), &instance_index, &s).await
+            {
                 let x: Arc<dyn SkimItem> = Arc::new(ErrorItem {
-                    message: format!("{}", msg),
+                    message: format!("{}", err),
+                    source_profile: profile,
+                    source_region: region,
                 });
-                s.send(x).expect("send error");
+                let _ = s.send(x);
             }
-        }
-    });
+        });
+    }
 
     r
 }
 
-async fn get_instances(client: &Client, args: &Args) -> Result<Vec<InstanceItem>> {
+/// Stream one (profile, region) source's instances into `s`: cached rows
+/// first (if any), then a live refresh that overwrites the cache on success.
+/// A failed live refresh bubbles up as `Err` so the caller can report it
+/// without losing whatever cached rows already streamed.
+async fn query_source(
+    common: &CommonArgs,
+    profile: Option<&str>,
+    region: Option<&str>,
+    instance_index: &InstanceIndex,
+    s: &SkimItemSender,
+) -> Result<()> {
+    let cache = cache::Cache::open().ok();
+    let cache_key = cache::cache_key(profile, region, &common.filter);
+
+    if let Some(ref cache) = cache {
+        if let Ok(Some((raw, fetched_at))) = cache.get(&cache_key) {
+            let stale = chrono::Utc::now().timestamp() - fetched_at >= common.cache_ttl as i64;
+            for mut item in instance_items(raw, &name_rule_for(common)) {
+                item.stale = stale;
+                item.source_profile = profile.map(str::to_string);
+                item.source_region = region.map(str::to_string);
+                index_instance(instance_index, &item);
+                let x: Arc<dyn SkimItem> = Arc::new(item);
+                let _ = s.send(x);
+            }
+        }
+    }
+
+    let client = ec2_client(profile, region).await;
+    let instances = get_instances(&client, common).await?;
+
+    if let Some(ref cache) = cache {
+        let raw: Vec<Instance> = instances.iter().map(|i| i.instance.clone()).collect();
+        // A failed write here must never drop the previous, still-usable
+        // cached row.
+        let _ = cache.put(&cache_key, &raw, chrono::Utc::now().timestamp());
+    }
+
+    for mut item in instances {
+        item.source_profile = profile.map(str::to_string);
+        item.source_region = region.map(str::to_string);
+        index_instance(instance_index, &item);
+        let x: Arc<dyn SkimItem> = Arc::new(item);
+        let _ = s.send(x);
+    }
+
+    Ok(())
+}
+
+fn name_rule_for(common: &CommonArgs) -> NameRule {
+    if let Some(ref tag) = common.name_tag {
+        NameRule::Tag(Box::new(tag.to_string()))
+    } else if common.name_host {
+        NameRule::Host
+    } else if common.name_id {
+        NameRule::InstanceID
+    } else {
+        NameRule::Tag(Box::new("Name".to_string()))
+    }
+}
+
+/// Apply a name rule to raw `Instance`s, whether they came from a live
+/// `describe_instances` call or the on-disk cache.
+fn instance_items(raw: Vec<Instance>, name_rule: &NameRule) -> Vec<InstanceItem> {
+    raw.into_iter()
+        .map(|i| {
+            let mut item: InstanceItem = i.into();
+            item.name_rule = Box::new(name_rule.clone());
+            item
+        })
+        .collect()
+}
+
+async fn get_instances(client: &Client, common: &CommonArgs) -> Result<Vec<InstanceItem>> {
     let mut instances_query = client.describe_instances();
-    for f in &args.filter {
+    for f in &common.filter {
         let filter = ec2::model::Filter::builder();
         instances_query = instances_query.filters(
             match f.split_once('=') {
@@ -273,25 +662,10 @@ async fn get_instances(client: &Client, args: &Args) -> Result<Vec<InstanceItem>
         .reservations()
         .ok_or_else(|| eyre!("no reservations"))?;
 
-    let name_rule: Box<NameRule> = Box::new(if let Some(ref tag) = args.name_tag {
-        NameRule::Tag(Box::new(tag.to_string()))
-    } else if args.name_host {
-        NameRule::Host
-    } else if args.name_id {
-        NameRule::InstanceID
-    } else {
-        NameRule::Tag(Box::new("Name".to_string()))
-    });
-
-    let instances: Vec<InstanceItem> = reservations
+    let raw: Vec<Instance> = reservations
         .iter()
         .flat_map(|r| r.instances().expect("instances").iter().cloned())
-        .map(|i| {
-            let mut item: InstanceItem = i.into();
-            item.name_rule = name_rule.clone();
-            item
-        })
         .collect();
 
-    Ok(instances)
+    Ok(instance_items(raw, &name_rule_for(common)))
 }